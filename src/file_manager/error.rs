@@ -0,0 +1,41 @@
+use std::fmt;
+
+/// Errors that can occur while opening or validating a database file.
+#[derive(Debug)]
+pub enum FileHandleError {
+    Io(std::io::Error),
+    InvalidMagic,
+    UnsupportedVersion(u8),
+    PageSizeMismatch { expected: usize, actual: usize },
+}
+
+impl fmt::Display for FileHandleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FileHandleError::Io(e) => write!(f, "I/O error: {e}"),
+            FileHandleError::InvalidMagic => write!(f, "not a valid database file (bad magic)"),
+            FileHandleError::UnsupportedVersion(v) => {
+                write!(f, "unsupported file format version: {v}")
+            }
+            FileHandleError::PageSizeMismatch { expected, actual } => write!(
+                f,
+                "file was created with a {actual}-byte page size, but {expected} was requested"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FileHandleError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FileHandleError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for FileHandleError {
+    fn from(e: std::io::Error) -> Self {
+        FileHandleError::Io(e)
+    }
+}
@@ -1,89 +1,231 @@
-use bytes::{BufMut, BytesMut};
-use std::fs::File;
-use std::io::Result;
-use std::io::{ErrorKind, Read, Seek, SeekFrom, Write};
+use bytes::{Buf, BufMut, BytesMut};
+use std::fs::{File, OpenOptions};
+use std::io::{Error, ErrorKind, Result};
+use std::path::Path;
 
-const PAGE_SIZE: usize = 4096;
+use super::error::FileHandleError;
+use super::journal::Journal;
+use super::positioned_io;
 
+/// Page size used by [`FileHandle::new`], which doesn't let the caller pick
+/// one explicitly.
+pub const DEFAULT_PAGE_SIZE: usize = 4096;
+
+/// Identifies this file as a database created by this crate.
+const MAGIC: &[u8; 4] = b"RSDB";
+/// Bumped whenever the on-disk meta page layout changes incompatibly.
+const FORMAT_VERSION: u8 = 1;
+/// Fixed-size portion of the meta page: magic, version, page-size exponent,
+/// page count, the three counters, and the free-list length. The free list
+/// itself follows as `free_len` extra `u32`s, so the meta page's total size
+/// depends on how many pages are currently free rather than on `page_size`.
+const HEADER_PREFIX_SIZE: usize = 4 + 1 + 1 + 4 + 8 + 8 + 8 + 4;
+
+/// The fixed binary layout that lives at the start of the file: magic,
+/// version, page-size exponent, page counters, and the free-page list.
+/// Every data page is addressed at `(page_num + 1) * page_size`, so this
+/// header never collides with page data as long as `page_size` is at least
+/// `HEADER_PREFIX_SIZE` bytes and the free list stays within
+/// `max_free_pages()`. Smaller page sizes are not checked for.
+struct MetaPage {
+    page_size_exp: u8,
+    num_pages: usize,
+    read_count: usize,
+    write_count: usize,
+    append_count: usize,
+    free_pages: Vec<usize>,
+}
+
+impl MetaPage {
+    fn empty(page_size_exp: u8) -> Self {
+        Self {
+            page_size_exp,
+            num_pages: 0,
+            read_count: 0,
+            write_count: 0,
+            append_count: 0,
+            free_pages: Vec::new(),
+        }
+    }
+
+    fn encode(&self) -> BytesMut {
+        let mut buf = BytesMut::with_capacity(HEADER_PREFIX_SIZE + self.free_pages.len() * 4);
+        buf.put_slice(MAGIC);
+        buf.put_u8(FORMAT_VERSION);
+        buf.put_u8(self.page_size_exp);
+        buf.put_u32(self.num_pages as u32);
+        buf.put_u64(self.read_count as u64);
+        buf.put_u64(self.write_count as u64);
+        buf.put_u64(self.append_count as u64);
+        buf.put_u32(self.free_pages.len() as u32);
+        for page in &self.free_pages {
+            buf.put_u32(*page as u32);
+        }
+        buf
+    }
+
+    /// Reads and decodes the meta page from the start of `file`. Since the
+    /// page size itself lives inside the meta page, this can't assume a
+    /// page-sized read up front: it reads the fixed-size prefix first, then
+    /// the variable-length free list it describes.
+    fn read_from(file: &File) -> std::result::Result<Self, FileHandleError> {
+        let mut prefix = [0u8; HEADER_PREFIX_SIZE];
+        positioned_io::read_at(file, &mut prefix, 0)?;
+
+        let mut cursor = &prefix[..];
+        if cursor.len() < MAGIC.len() || &cursor[..MAGIC.len()] != MAGIC {
+            return Err(FileHandleError::InvalidMagic);
+        }
+        cursor.advance(MAGIC.len());
+
+        let version = cursor.get_u8();
+        if version != FORMAT_VERSION {
+            return Err(FileHandleError::UnsupportedVersion(version));
+        }
+
+        let page_size_exp = cursor.get_u8();
+        let num_pages = cursor.get_u32() as usize;
+        let read_count = cursor.get_u64() as usize;
+        let write_count = cursor.get_u64() as usize;
+        let append_count = cursor.get_u64() as usize;
+        let free_len = cursor.get_u32() as usize;
+
+        let mut free_list_raw = vec![0u8; free_len * 4];
+        positioned_io::read_at(file, &mut free_list_raw, HEADER_PREFIX_SIZE as u64)?;
+        let mut free_cursor = &free_list_raw[..];
+        let free_pages = (0..free_len)
+            .map(|_| free_cursor.get_u32() as usize)
+            .collect();
+
+        Ok(Self {
+            page_size_exp,
+            num_pages,
+            read_count,
+            write_count,
+            append_count,
+            free_pages,
+        })
+    }
+}
+
+#[derive(Debug)]
 pub struct FileHandle {
     num_pages: usize,
     file: File,
     read_count: usize,
     write_count: usize,
     append_count: usize,
+    free_pages: Vec<usize>,
+    page_size_exp: u8,
+    journal: Journal,
 }
 
 impl FileHandle {
-    pub fn new(mut file: File) -> std::result::Result<Self, std::io::Error> {
-        let f_size = file.metadata()?.len();
+    /// Opens (or creates) `path` with the default page size.
+    ///
+    /// Takes a path rather than an already-open `File` so the journal can
+    /// derive its sidecar `.journal` path from it; callers that previously
+    /// constructed a `FileHandle` from an open `File` need to pass the path
+    /// instead.
+    pub fn new(path: impl AsRef<Path>) -> std::result::Result<Self, FileHandleError> {
+        Self::with_page_size(path, DEFAULT_PAGE_SIZE)
+    }
+
+    /// Opens (or creates) `path`, using `page_size` bytes per page. `page_size`
+    /// must be a power of two. If the file already exists, its stored page
+    /// size must match `page_size` exactly; a mismatch is reported rather
+    /// than silently read with the wrong offsets.
+    pub fn with_page_size(
+        path: impl AsRef<Path>,
+        page_size: usize,
+    ) -> std::result::Result<Self, FileHandleError> {
+        let path = path.as_ref();
+        let requested_exp = page_size.trailing_zeros() as u8;
+
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
 
-        let (read_count, write_count, append_count, num_pages) = if f_size > 0 {
-            //read in counters
-            let mut counters_str = String::new();
-            file.read_to_string(&mut counters_str)?;
-            let split = counters_str.split("|");
-            let counters: Vec<usize> = split
-                .map(|s| s.trim())
-                .filter(|s| !s.is_empty() && !s.starts_with("0"))
-                // Should panic here, if we cant parse data we cant proceed
-                .map(|s| s.parse().unwrap())
-                .collect();
-            if counters.len() != 4 {
-                (0, 0, 0, 0)
-            } else {
-                // Safe because length is guaranteed to be 4
-                (
-                    *counters.get(0).unwrap(),
-                    *counters.get(1).unwrap(),
-                    *counters.get(2).unwrap(),
-                    *counters.get(3).unwrap(),
-                )
+        let f_size = file.metadata()?.len();
+        let meta = if f_size > 0 {
+            let meta = MetaPage::read_from(&file)?;
+            if meta.page_size_exp != requested_exp {
+                return Err(FileHandleError::PageSizeMismatch {
+                    expected: page_size,
+                    actual: 1usize << meta.page_size_exp,
+                });
             }
+            meta
         } else {
-            let counters_str = format!("{}|{}|{}|{}", 0, 0, 0, 0);
-            file.write(&counters_str.as_bytes())?;
-            (0, 0, 0, 0)
+            let meta = MetaPage::empty(requested_exp);
+            file.write_all_at_start(&meta.encode())?;
+            meta
         };
+
+        // The meta page (including its page-size exponent) is never
+        // journaled, so it's safe to read before recovering the journal:
+        // only data pages written via `write_page` need rolling back.
+        let journal_path = Journal::path_for(path);
+        let journal =
+            Journal::open_with_recovery(&journal_path, &mut file, 1usize << meta.page_size_exp)?;
+
         Ok(Self {
-            num_pages,
+            num_pages: meta.num_pages,
+            read_count: meta.read_count,
+            write_count: meta.write_count,
+            append_count: meta.append_count,
+            free_pages: meta.free_pages,
+            page_size_exp: meta.page_size_exp,
             file,
-            write_count,
-            read_count,
-            append_count,
+            journal,
         })
     }
 
-    fn write_counters(&mut self) -> Result<usize> {
-        let Self {
-            ref read_count,
-            ref write_count,
-            ref append_count,
-            ref num_pages,
-            ..
-        } = self;
-        let counters_str = format!(
-            "{}|{}|{}|{}",
-            read_count, write_count, append_count, num_pages
-        );
-        self.file.write(&counters_str.as_bytes())
+    /// The page size this file was created with, in bytes.
+    pub fn page_size(&self) -> usize {
+        1usize << self.page_size_exp
+    }
+
+    fn offset_of(&self, page_num: usize) -> u64 {
+        ((page_num as u64) + 1) * self.page_size() as u64
+    }
+
+    /// The most free pages the meta page can track without growing past
+    /// `page_size` and overrunning page 0's data.
+    fn max_free_pages(&self) -> usize {
+        (self.page_size() - HEADER_PREFIX_SIZE) / 4
+    }
+
+    fn write_meta(&mut self) -> Result<()> {
+        let meta = MetaPage {
+            page_size_exp: self.page_size_exp,
+            num_pages: self.num_pages,
+            read_count: self.read_count,
+            write_count: self.write_count,
+            append_count: self.append_count,
+            free_pages: self.free_pages.clone(),
+        };
+        self.file.write_all_at_start(&meta.encode())
     }
 
-    pub fn read_page(&mut self, page_num: usize, data: &mut BytesMut) -> Result<usize> {
+    pub fn read_page(&self, page_num: usize, data: &mut BytesMut) -> Result<usize> {
         if page_num >= self.num_pages {
             Err(std::io::Error::new(
                 ErrorKind::InvalidInput,
                 "Index Out of Bounds",
             ))
         } else {
-            self.read_from(((page_num + 1) * PAGE_SIZE) as u64, data)
+            self.read_from(self.offset_of(page_num), data)
         }
     }
 
     // TODO: figure out why we have to write into buf, then data...
-    fn read_from(&mut self, pos: u64, data: &mut BytesMut) -> Result<usize> {
-        self.file.seek(SeekFrom::Start(pos))?;
-        let mut buf: [u8; PAGE_SIZE] = [0; PAGE_SIZE];
-        let bytes_read = self.file.read(&mut buf)?;
+    fn read_from(&self, pos: u64, data: &mut BytesMut) -> Result<usize> {
+        let mut buf = vec![0u8; self.page_size()];
+        let bytes_read = positioned_io::read_at(&self.file, &mut buf, pos)?;
         data.put_slice(&buf);
         Ok(bytes_read)
     }
@@ -95,96 +237,165 @@ impl FileHandle {
                 "Index Out of Bounds",
             ))
         } else {
-            let bytes_written = self.write_to(((page_num + 1) * PAGE_SIZE) as u64, data)?;
+            let offset = self.offset_of(page_num);
+            let mut before_image = BytesMut::with_capacity(self.page_size());
+            self.read_from(offset, &mut before_image)?;
+            self.journal.record_before_image(page_num, &before_image)?;
+
+            let bytes_written = self.write_to(offset, data)?;
             self.write_count += 1;
+            self.write_meta()?;
             Ok(bytes_written)
         }
     }
-    fn write_to(&mut self, pos: u64, data: &BytesMut) -> Result<usize> {
-        self.file.seek(SeekFrom::Start(pos))?;
-        self.file.write(&data)
+
+    /// Makes the writes since the last commit durable: fsyncs the journal,
+    /// flushes and fsyncs the main file, then truncates the journal so the
+    /// next write starts a fresh transaction.
+    pub fn commit(&mut self) -> Result<()> {
+        self.journal.sync()?;
+        self.file.sync_all()?;
+        self.journal.clear()
     }
 
-    pub fn append_page(&mut self, data: &BytesMut) -> Result<usize> {
-        let bytes_written = self.write_to(((self.num_pages + 1) * PAGE_SIZE) as u64, data)?;
+    fn write_to(&self, pos: u64, data: &BytesMut) -> Result<usize> {
+        positioned_io::write_at(&self.file, data, pos)
+    }
+
+    /// Pops a logically-deleted page off the free list, if one is available,
+    /// so callers can reuse it instead of growing the file. Mirrors persy's
+    /// `mark_allocated`.
+    fn allocate_page(&mut self) -> Option<usize> {
+        self.free_pages.pop()
+    }
+
+    /// Returns `page_num` to the free list so a future `append_page` call
+    /// reuses it instead of extending the file. Mirrors persy's
+    /// `trim_or_free_page`. The free list is encoded in the meta page
+    /// alongside a fixed-size prefix at the start of the file, so it's
+    /// capped at `max_free_pages()` entries: past that, tracking one more
+    /// free page would grow the meta page past `page_size` and overrun
+    /// page 0's data.
+    pub fn free_page(&mut self, page_num: usize) -> Result<()> {
+        if self.free_pages.len() >= self.max_free_pages() {
+            return Err(Error::other(
+                "free list is full for this page size; page_size is too small to track more free pages",
+            ));
+        }
+        self.free_pages.push(page_num);
+        self.write_meta()
+    }
+
+    /// Reserves a page number to write into: reuses a freed page if one is
+    /// available, otherwise grows the file by one page. The caller is
+    /// responsible for filling in its contents, e.g. via `write_page`.
+    pub fn reserve_page(&mut self) -> Result<usize> {
+        if let Some(page_num) = self.allocate_page() {
+            self.append_count += 1;
+            self.write_meta()?;
+            return Ok(page_num);
+        }
+
+        let page_num = self.num_pages;
         self.num_pages += 1;
         self.append_count += 1;
-        Ok(bytes_written)
+        self.write_meta()?;
+        Ok(page_num)
+    }
+
+    /// Reserves a page and writes `data` into it via `write_page`, so a
+    /// reused (freed) page gets its before-image journaled the same as any
+    /// other page mutation rather than being written directly and left
+    /// without crash-safety coverage.
+    pub fn append_page(&mut self, data: &BytesMut) -> Result<usize> {
+        let page_num = self.reserve_page()?;
+        self.write_page(page_num, data)
     }
 
     pub fn get_num_pages(&self) -> usize {
         self.num_pages
     }
 }
+
+/// Small helper so `MetaPage` writes don't need `Seek` sprinkled everywhere.
+trait WriteAtStart {
+    fn write_all_at_start(&mut self, data: &[u8]) -> Result<()>;
+}
+
+impl WriteAtStart for File {
+    fn write_all_at_start(&mut self, data: &[u8]) -> Result<()> {
+        positioned_io::write_at(self, data, 0).map(|_| ())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use bytes::{BufMut, BytesMut};
     use std::fs::OpenOptions;
+    use std::io::Write;
+
+    /// Removes the data file and its journal sidecar, if either exists.
+    fn cleanup(file_name: &str) {
+        let _ = std::fs::remove_file(file_name);
+        let _ = std::fs::remove_file(format!("{file_name}.journal"));
+    }
 
     #[test]
     fn read_write_to_new_file() {
         let file_name = "./test_files/read_write_to_new_file";
-        let file = OpenOptions::new()
-            .read(true)
-            .write(true)
-            .create_new(true)
-            .open(file_name)
-            .unwrap();
+        cleanup(file_name);
 
-        let mut file_handle_res = FileHandle::new(file);
+        let file_handle_res = FileHandle::new(file_name);
         assert!(file_handle_res.is_ok());
         let mut file_handle = file_handle_res.unwrap();
+        let page_size = file_handle.page_size();
 
-        let mut page = BytesMut::with_capacity(PAGE_SIZE);
+        let mut page = BytesMut::with_capacity(page_size);
         const DATA: &[u8] = b"Test Data";
         page.put(&DATA[..]);
-        page.put(&[0; PAGE_SIZE - DATA.len()][..]);
+        page.put(&vec![0u8; page_size - DATA.len()][..]);
 
         let mut res = file_handle.append_page(&page);
         assert!(res.is_ok());
-        assert_eq!(res.unwrap(), PAGE_SIZE);
+        assert_eq!(res.unwrap(), page_size);
 
-        let mut buf: BytesMut = BytesMut::with_capacity(PAGE_SIZE);
+        let mut buf: BytesMut = BytesMut::with_capacity(page_size);
         res = file_handle.read_page(0, &mut buf);
         assert!(res.is_ok());
-        assert_eq!(res.unwrap(), PAGE_SIZE);
+        assert_eq!(res.unwrap(), page_size);
 
         assert_eq!(page, buf);
-        std::fs::remove_file(file_name);
+        cleanup(file_name);
     }
 
     #[test]
     fn read_write_multiple_pages() {
         let file_name = "./test_files/read_write_multiple_pages";
-        let file = OpenOptions::new()
-            .read(true)
-            .write(true)
-            .create_new(true)
-            .open(file_name)
-            .unwrap();
+        cleanup(file_name);
 
         // Create file handle
-        let mut file_handle_res = FileHandle::new(file);
+        let file_handle_res = FileHandle::new(file_name);
         assert!(file_handle_res.is_ok());
         let mut file_handle = file_handle_res.unwrap();
+        let page_size = file_handle.page_size();
 
         // Create first page
-        let mut page = BytesMut::with_capacity(PAGE_SIZE);
+        let mut page = BytesMut::with_capacity(page_size);
         const DATA: &[u8] = b"Test Data on page 1";
         page.put(&DATA[..]);
-        page.put(&[0; PAGE_SIZE - DATA.len()][..]);
+        page.put(&vec![0u8; page_size - DATA.len()][..]);
 
         // Append it
         let mut res = file_handle.append_page(&page);
         assert!(res.is_ok());
-        assert_eq!(res.unwrap(), PAGE_SIZE);
+        assert_eq!(res.unwrap(), page_size);
 
         // Create output buffer and read first page
-        let mut buf: BytesMut = BytesMut::with_capacity(PAGE_SIZE);
+        let mut buf: BytesMut = BytesMut::with_capacity(page_size);
         res = file_handle.read_page(0, &mut buf);
         assert!(res.is_ok());
-        assert_eq!(res.unwrap(), PAGE_SIZE);
+        assert_eq!(res.unwrap(), page_size);
 
         // Check that the data we get back is the same
         assert_eq!(page, buf);
@@ -193,19 +404,169 @@ mod tests {
         page.clear();
         const DATA2: &[u8] = b"New data for page 1";
         page.put(&DATA2[..]);
-        page.put(&[0; PAGE_SIZE - DATA2.len()][..]);
+        page.put(&vec![0u8; page_size - DATA2.len()][..]);
 
         // Write it
         res = file_handle.write_page(0, &page);
         assert!(res.is_ok());
-        assert_eq!(res.unwrap(), PAGE_SIZE);
+        assert_eq!(res.unwrap(), page_size);
+        file_handle.commit().unwrap();
 
         buf.clear();
         res = file_handle.read_page(0, &mut buf);
         assert!(res.is_ok());
-        assert_eq!(res.unwrap(), PAGE_SIZE);
+        assert_eq!(res.unwrap(), page_size);
 
         assert_eq!(page, buf);
-        std::fs::remove_file(file_name);
+        cleanup(file_name);
+    }
+
+    #[test]
+    fn free_page_is_reused_by_append() {
+        let file_name = "./test_files/free_page_is_reused_by_append";
+        cleanup(file_name);
+
+        let mut file_handle = FileHandle::new(file_name).unwrap();
+        let page_size = file_handle.page_size();
+
+        let mut page = BytesMut::with_capacity(page_size);
+        page.put(&b"first"[..]);
+        page.put(&vec![0u8; page_size - 5][..]);
+        file_handle.append_page(&page).unwrap();
+        file_handle.append_page(&page).unwrap();
+        assert_eq!(file_handle.get_num_pages(), 2);
+
+        file_handle.free_page(0).unwrap();
+
+        let mut reused = BytesMut::with_capacity(page_size);
+        reused.put(&b"reused"[..]);
+        reused.put(&vec![0u8; page_size - 6][..]);
+        file_handle.append_page(&reused).unwrap();
+
+        // The free page was reused in place, so the page count shouldn't grow.
+        assert_eq!(file_handle.get_num_pages(), 2);
+
+        let mut buf = BytesMut::with_capacity(page_size);
+        file_handle.read_page(0, &mut buf).unwrap();
+        assert_eq!(reused, buf);
+
+        cleanup(file_name);
+    }
+
+    #[test]
+    fn rejects_file_with_bad_magic() {
+        let file_name = "./test_files/rejects_file_with_bad_magic";
+        cleanup(file_name);
+        {
+            let mut file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create_new(true)
+                .open(file_name)
+                .unwrap();
+            file.write_all(&[0u8; DEFAULT_PAGE_SIZE]).unwrap();
+        }
+
+        let err = FileHandle::new(file_name).unwrap_err();
+        assert!(matches!(err, FileHandleError::InvalidMagic));
+
+        cleanup(file_name);
+    }
+
+    #[test]
+    fn crash_mid_write_is_rolled_back_on_reopen() {
+        let file_name = "./test_files/crash_mid_write_is_rolled_back_on_reopen";
+        cleanup(file_name);
+
+        let mut page = BytesMut::with_capacity(DEFAULT_PAGE_SIZE);
+        page.put(&b"original"[..]);
+        page.put(&[0; DEFAULT_PAGE_SIZE - 8][..]);
+
+        {
+            let mut file_handle = FileHandle::new(file_name).unwrap();
+            file_handle.append_page(&page).unwrap();
+            file_handle.commit().unwrap();
+
+            // Write a new version of the page but "crash" before commit()
+            // truncates the journal: the before-image stays on disk.
+            let mut overwrite = BytesMut::with_capacity(DEFAULT_PAGE_SIZE);
+            overwrite.put(&b"uncommitted"[..]);
+            overwrite.put(&[0; DEFAULT_PAGE_SIZE - 11][..]);
+            file_handle.write_page(0, &overwrite).unwrap();
+        }
+
+        // Reopening should roll the page back to its last committed state.
+        let file_handle = FileHandle::new(file_name).unwrap();
+        let mut buf = BytesMut::with_capacity(DEFAULT_PAGE_SIZE);
+        file_handle.read_page(0, &mut buf).unwrap();
+        assert_eq!(page, buf);
+
+        cleanup(file_name);
+    }
+
+    #[test]
+    fn eight_kib_pages_round_trip() {
+        let file_name = "./test_files/eight_kib_pages_round_trip";
+        cleanup(file_name);
+
+        const PAGE_SIZE: usize = 8192;
+        {
+            let mut file_handle = FileHandle::with_page_size(file_name, PAGE_SIZE).unwrap();
+            let mut page = BytesMut::with_capacity(PAGE_SIZE);
+            page.put(&b"big page"[..]);
+            page.put(&vec![0u8; PAGE_SIZE - 8][..]);
+            file_handle.append_page(&page).unwrap();
+            file_handle.commit().unwrap();
+        }
+
+        // Reopening with the same page size reads back correctly...
+        let file_handle = FileHandle::with_page_size(file_name, PAGE_SIZE).unwrap();
+        assert_eq!(file_handle.page_size(), PAGE_SIZE);
+        let mut buf = BytesMut::with_capacity(PAGE_SIZE);
+        file_handle.read_page(0, &mut buf).unwrap();
+        assert_eq!(&buf[0..8], b"big page");
+
+        // ...but asking for a different page size is rejected rather than
+        // silently misreading the file.
+        let err = FileHandle::with_page_size(file_name, DEFAULT_PAGE_SIZE).unwrap_err();
+        assert!(matches!(err, FileHandleError::PageSizeMismatch { .. }));
+
+        cleanup(file_name);
+    }
+
+    #[test]
+    fn free_list_is_capped_instead_of_overrunning_page_zero() {
+        let file_name = "./test_files/free_list_is_capped_instead_of_overrunning_page_zero";
+        cleanup(file_name);
+
+        // A tiny page size keeps `max_free_pages()` small enough to hit in a
+        // unit test.
+        const PAGE_SIZE: usize = 64;
+        let mut file_handle = FileHandle::with_page_size(file_name, PAGE_SIZE).unwrap();
+        let max_free = file_handle.max_free_pages();
+
+        let mut page0 = BytesMut::with_capacity(PAGE_SIZE);
+        page0.put(&b"PAGE0-ORIGINAL"[..]);
+        page0.put(&vec![0u8; PAGE_SIZE - 14][..]);
+        file_handle.append_page(&page0).unwrap();
+
+        let filler = BytesMut::zeroed(PAGE_SIZE);
+        for _ in 0..max_free {
+            file_handle.append_page(&filler).unwrap();
+        }
+        for page_num in 1..=max_free {
+            file_handle.free_page(page_num).unwrap();
+        }
+
+        // The free list is already at capacity: freeing one more page must
+        // be rejected rather than growing the meta page into page 0's data.
+        let err = file_handle.free_page(max_free + 1).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::Other);
+
+        let mut buf = BytesMut::with_capacity(PAGE_SIZE);
+        file_handle.read_page(0, &mut buf).unwrap();
+        assert_eq!(buf, page0);
+
+        cleanup(file_name);
     }
 }
@@ -0,0 +1,219 @@
+use bytes::{Buf, BufMut, BytesMut};
+use std::collections::HashSet;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Result, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const MAGIC: &[u8; 4] = b"WALJ";
+const HEADER_SIZE: usize = MAGIC.len() + 4 + 4 + 1;
+const FRAME_HEADER_SIZE: usize = 4 + 4;
+
+/// A before-image write-ahead journal, modeled on polodb's journal: before a
+/// page in the main file is overwritten, its old contents are appended here
+/// so that a crash between the first and last write of a transaction can be
+/// rolled back on the next open.
+#[derive(Debug)]
+pub(crate) struct Journal {
+    file: File,
+    salt: u32,
+    frame_count: u32,
+}
+
+impl Journal {
+    /// Derives the sidecar path for a main database file, e.g. `db.rsdb` ->
+    /// `db.rsdb.journal`.
+    pub(crate) fn path_for(main_path: &Path) -> PathBuf {
+        let mut journal_path = main_path.as_os_str().to_os_string();
+        journal_path.push(".journal");
+        PathBuf::from(journal_path)
+    }
+
+    /// Opens (creating if necessary) the journal at `journal_path`. If an
+    /// uncommitted transaction is found, its frames are rolled back into
+    /// `main_file` before the journal is reset to the empty state.
+    /// `page_size` must be the main file's configured page size, since the
+    /// journal's frames don't carry their own length.
+    pub(crate) fn open_with_recovery(
+        journal_path: &Path,
+        main_file: &mut File,
+        page_size: usize,
+    ) -> Result<Self> {
+        if journal_path.exists() {
+            let mut existing = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(journal_path)?;
+            if let Some(header) = JournalHeader::read(&mut existing)? {
+                if !header.committed && header.frame_count > 0 {
+                    Self::rollback(&mut existing, &header, main_file, page_size)?;
+                }
+            }
+        }
+        Self::fresh(journal_path)
+    }
+
+    fn fresh(journal_path: &Path) -> Result<Self> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(journal_path)?;
+        let salt = new_salt();
+        JournalHeader {
+            salt,
+            frame_count: 0,
+            committed: true,
+        }
+        .write(&mut file)?;
+        Ok(Self {
+            file,
+            salt,
+            frame_count: 0,
+        })
+    }
+
+    /// Appends the before-image of `page_num` to the journal, opening an
+    /// uncommitted transaction if one isn't already open.
+    pub(crate) fn record_before_image(&mut self, page_num: usize, before_image: &[u8]) -> Result<()> {
+        if self.frame_count == 0 {
+            self.write_header(false)?;
+        }
+
+        let checksum = checksum(self.salt, page_num as u32, before_image);
+        let mut frame = BytesMut::with_capacity(FRAME_HEADER_SIZE + before_image.len());
+        frame.put_u32(page_num as u32);
+        frame.put_u32(checksum);
+        frame.put_slice(before_image);
+
+        self.file.seek(SeekFrom::End(0))?;
+        self.file.write_all(&frame)?;
+        self.frame_count += 1;
+        self.write_header(false)
+    }
+
+    /// Fsyncs the journal so its frames are durable before the caller
+    /// fsyncs the main file.
+    pub(crate) fn sync(&mut self) -> Result<()> {
+        self.file.sync_all()
+    }
+
+    /// Resets the journal to the empty, committed state, ready for the next
+    /// transaction.
+    pub(crate) fn clear(&mut self) -> Result<()> {
+        self.file.set_len(0)?;
+        self.salt = new_salt();
+        self.frame_count = 0;
+        self.write_header(true)
+    }
+
+    fn write_header(&mut self, committed: bool) -> Result<()> {
+        self.file.seek(SeekFrom::Start(0))?;
+        JournalHeader {
+            salt: self.salt,
+            frame_count: self.frame_count,
+            committed,
+        }
+        .write(&mut self.file)
+    }
+
+    fn rollback(
+        file: &mut File,
+        header: &JournalHeader,
+        main_file: &mut File,
+        page_size: usize,
+    ) -> Result<()> {
+        file.seek(SeekFrom::Start(HEADER_SIZE as u64))?;
+
+        // Only a page's first frame holds its last committed contents; later
+        // frames for the same page are intermediate states of this transaction.
+        let mut restored = HashSet::new();
+        for _ in 0..header.frame_count {
+            let mut frame_header = [0u8; FRAME_HEADER_SIZE];
+            if file.read_exact(&mut frame_header).is_err() {
+                break; // incomplete record: stop replay
+            }
+            let mut cursor = &frame_header[..];
+            let page_num = cursor.get_u32();
+            let expected_checksum = cursor.get_u32();
+
+            let mut before_image = vec![0u8; page_size];
+            if file.read_exact(&mut before_image).is_err() {
+                break; // incomplete record: stop replay
+            }
+
+            if checksum(header.salt, page_num, &before_image) != expected_checksum {
+                break; // corrupt record: stop replay
+            }
+            if !restored.insert(page_num) {
+                continue;
+            }
+
+            main_file.seek(SeekFrom::Start((page_num as u64 + 1) * page_size as u64))?;
+            main_file.write_all(&before_image)?;
+        }
+
+        main_file.sync_all()
+    }
+}
+
+struct JournalHeader {
+    salt: u32,
+    frame_count: u32,
+    committed: bool,
+}
+
+impl JournalHeader {
+    fn read(file: &mut File) -> Result<Option<Self>> {
+        if (file.metadata()?.len() as usize) < HEADER_SIZE {
+            return Ok(None);
+        }
+
+        file.seek(SeekFrom::Start(0))?;
+        let mut raw = [0u8; HEADER_SIZE];
+        file.read_exact(&mut raw)?;
+
+        let mut buf = &raw[..];
+        if &buf[..MAGIC.len()] != MAGIC {
+            return Ok(None);
+        }
+        buf.advance(MAGIC.len());
+
+        let salt = buf.get_u32();
+        let frame_count = buf.get_u32();
+        let committed = buf.get_u8() != 0;
+        Ok(Some(Self {
+            salt,
+            frame_count,
+            committed,
+        }))
+    }
+
+    fn write(&self, file: &mut File) -> Result<()> {
+        let mut buf = BytesMut::with_capacity(HEADER_SIZE);
+        buf.put_slice(MAGIC);
+        buf.put_u32(self.salt);
+        buf.put_u32(self.frame_count);
+        buf.put_u8(self.committed as u8);
+        file.write_all(&buf)
+    }
+}
+
+fn new_salt() -> u32 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0)
+        ^ 0x9E37_79B9
+}
+
+fn checksum(salt: u32, page_num: u32, data: &[u8]) -> u32 {
+    let mut sum = salt ^ page_num;
+    for chunk in data.chunks(4) {
+        let mut word = [0u8; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum = sum.wrapping_add(u32::from_le_bytes(word));
+    }
+    sum
+}
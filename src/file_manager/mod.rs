@@ -0,0 +1,4 @@
+pub mod error;
+pub mod file_handle;
+mod journal;
+mod positioned_io;
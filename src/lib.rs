@@ -0,0 +1,4 @@
+pub mod buffer_pool;
+pub mod file_manager;
+pub mod record_store;
+pub mod store_policy;
@@ -0,0 +1,215 @@
+use std::io::{Error, ErrorKind, Result};
+
+use bytes::{Buf, BufMut, BytesMut};
+
+use crate::file_manager::file_handle::FileHandle;
+
+/// Header written on a record's first page: a `next_page` link followed by
+/// the record's total length.
+const FIRST_HEADER_SIZE: usize = 4 + 8;
+/// Header written on every overflow page after the first: just the
+/// `next_page` link.
+const CONT_HEADER_SIZE: usize = 4;
+
+/// A store for variable-length byte records, layered on top of
+/// [`FileHandle`]. Records too big for a single page are split across a
+/// chain of overflow pages linked by a `next_page` field, similar to how ogg
+/// packets span pages; the first page additionally carries the record's
+/// total length so [`RecordStore::get`] knows how much of the last page's
+/// payload is valid.
+pub struct RecordStore {
+    file_handle: FileHandle,
+}
+
+impl RecordStore {
+    pub fn new(file_handle: FileHandle) -> Self {
+        Self { file_handle }
+    }
+
+    /// Writes `bytes` as a new record, chaining it across as many pages as
+    /// needed, and returns its record id (the page number of its first
+    /// page).
+    pub fn put(&mut self, bytes: &[u8]) -> Result<usize> {
+        let page_size = self.file_handle.page_size();
+        let first_capacity = page_size - FIRST_HEADER_SIZE;
+        let cont_capacity = page_size - CONT_HEADER_SIZE;
+
+        let chunk_lens = chunk_lengths(bytes.len(), first_capacity, cont_capacity);
+        let pages = chunk_lens
+            .iter()
+            .map(|_| self.file_handle.reserve_page())
+            .collect::<Result<Vec<usize>>>()?;
+
+        let mut offset = 0;
+        for (i, &page_num) in pages.iter().enumerate() {
+            let chunk = &bytes[offset..offset + chunk_lens[i]];
+            offset += chunk_lens[i];
+            let next = pages.get(i + 1).copied();
+
+            let capacity = if i == 0 { first_capacity } else { cont_capacity };
+            let mut page = BytesMut::with_capacity(page_size);
+            page.put_u32(encode_next(next));
+            if i == 0 {
+                page.put_u64(bytes.len() as u64);
+            }
+            page.put_slice(chunk);
+            page.put_slice(&vec![0u8; capacity - chunk.len()]);
+
+            self.file_handle.write_page(page_num, &page)?;
+        }
+
+        Ok(pages[0])
+    }
+
+    /// Reads back the record written at `record_id`.
+    pub fn get(&self, record_id: usize) -> Result<BytesMut> {
+        let page_size = self.file_handle.page_size();
+        let first_capacity = page_size - FIRST_HEADER_SIZE;
+        let cont_capacity = page_size - CONT_HEADER_SIZE;
+
+        let mut raw = BytesMut::with_capacity(page_size);
+        self.file_handle.read_page(record_id, &mut raw)?;
+        let mut cursor = &raw[..];
+        let mut next_page = decode_next(cursor.get_u32());
+        let total_len = cursor.get_u64() as usize;
+
+        let mut out = BytesMut::with_capacity(total_len);
+        let mut remaining = total_len.min(first_capacity);
+        out.put_slice(&cursor[..remaining]);
+        remaining = total_len - remaining;
+
+        while remaining > 0 {
+            let page_num = next_page.ok_or_else(|| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    "record chain ended before its declared length",
+                )
+            })?;
+
+            let mut raw = BytesMut::with_capacity(page_size);
+            self.file_handle.read_page(page_num, &mut raw)?;
+            let mut cursor = &raw[..];
+            next_page = decode_next(cursor.get_u32());
+
+            let len = remaining.min(cont_capacity);
+            out.put_slice(&cursor[..len]);
+            remaining -= len;
+        }
+
+        Ok(out)
+    }
+
+    /// Returns every page in `record_id`'s chain to the free list.
+    pub fn delete(&mut self, record_id: usize) -> Result<()> {
+        let page_size = self.file_handle.page_size();
+        let mut next_page = Some(record_id);
+        while let Some(page_num) = next_page {
+            let mut raw = BytesMut::with_capacity(page_size);
+            self.file_handle.read_page(page_num, &mut raw)?;
+            next_page = decode_next((&raw[..]).get_u32());
+            self.file_handle.free_page(page_num)?;
+        }
+        Ok(())
+    }
+}
+
+/// Splits a record of `total` bytes into per-page chunk lengths: the first
+/// page holds up to `first_capacity` bytes, every page after that holds up
+/// to `cont_capacity`. Always returns at least one length, so empty records
+/// still get a (header-only) page.
+fn chunk_lengths(total: usize, first_capacity: usize, cont_capacity: usize) -> Vec<usize> {
+    let mut lens = Vec::new();
+    let mut remaining = total;
+
+    let first = remaining.min(first_capacity);
+    lens.push(first);
+    remaining -= first;
+
+    while remaining > 0 {
+        let len = remaining.min(cont_capacity);
+        lens.push(len);
+        remaining -= len;
+    }
+
+    lens
+}
+
+/// `next_page` links are offset by one so that `0` can mean "no next page"
+/// without colliding with the legitimate page number `0`.
+fn encode_next(next: Option<usize>) -> u32 {
+    match next {
+        None => 0,
+        Some(page_num) => page_num as u32 + 1,
+    }
+}
+
+fn decode_next(raw: u32) -> Option<usize> {
+    if raw == 0 {
+        None
+    } else {
+        Some(raw as usize - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn open(file_name: &str) -> RecordStore {
+        cleanup(file_name);
+        RecordStore::new(FileHandle::new(file_name).unwrap())
+    }
+
+    /// Removes the data file and its journal sidecar, if either exists.
+    fn cleanup(file_name: &str) {
+        let _ = std::fs::remove_file(file_name);
+        let _ = std::fs::remove_file(format!("{file_name}.journal"));
+    }
+
+    #[test]
+    fn put_then_get_round_trips_a_small_record() {
+        let file_name = "./test_files/record_store_small_round_trip";
+        let mut store = open(file_name);
+
+        let record_id = store.put(b"hello, record store").unwrap();
+        let record = store.get(record_id).unwrap();
+
+        assert_eq!(&record[..], b"hello, record store");
+        cleanup(file_name);
+    }
+
+    #[test]
+    fn put_then_get_round_trips_a_record_spanning_several_pages() {
+        let file_name = "./test_files/record_store_multi_page_round_trip";
+        let mut store = open(file_name);
+
+        let page_size = store.file_handle.page_size();
+        let big: Vec<u8> = (0..page_size * 3 + 17).map(|i| (i % 251) as u8).collect();
+
+        let record_id = store.put(&big).unwrap();
+        let record = store.get(record_id).unwrap();
+
+        assert_eq!(&record[..], &big[..]);
+        cleanup(file_name);
+    }
+
+    #[test]
+    fn delete_frees_pages_for_reuse() {
+        let file_name = "./test_files/record_store_delete_frees_pages";
+        let mut store = open(file_name);
+
+        let page_size = store.file_handle.page_size();
+        let big: Vec<u8> = vec![7u8; page_size * 2];
+        let first_id = store.put(&big).unwrap();
+        let pages_before = store.file_handle.get_num_pages();
+
+        store.delete(first_id).unwrap();
+        let second_id = store.put(&big).unwrap();
+
+        // The freed chain's pages were reused, so no new pages were appended.
+        assert_eq!(store.file_handle.get_num_pages(), pages_before);
+        assert_eq!(&store.get(second_id).unwrap()[..], &big[..]);
+
+        cleanup(file_name);
+    }
+}